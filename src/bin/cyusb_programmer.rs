@@ -1,3 +1,4 @@
+use cyusb::ProgressEvent;
 use log::{error, info};
 use rusb::UsbContext;
 use std::path::PathBuf;
@@ -42,6 +43,21 @@ struct Opt {
     /// RAM, I2C, or SPI
     #[structopt(short, long, default_value = "RAM")]
     target: Target,
+
+    /// Reprogram even if the image already matches the target's
+    /// stored hash (I2C and SPI only)
+    #[structopt(short, long)]
+    force: bool,
+}
+
+fn log_progress(event: ProgressEvent) {
+    let percent = (event.bytes_done * 100)
+        .checked_div(event.bytes_total)
+        .unwrap_or(100);
+    info!(
+        "{:?}: 0x{:08x} ({}%)",
+        event.phase, event.address, percent
+    );
 }
 
 fn main() {
@@ -52,10 +68,6 @@ fn main() {
         .init();
 
     let opt = Opt::from_args();
-    if opt.target != Target::Ram {
-        error!("only the RAM target works currently");
-        exit(1);
-    }
 
     let context = rusb::Context::new().unwrap();
 
@@ -76,10 +88,28 @@ fn main() {
     }
 
     if let Some(device) = devices.get(opt.index) {
-        if let Err(err) =
-            cyusb::program_fx3_ram(&device.open().unwrap(), &opt.image)
-        {
-            error!("program_fx3_ram failed: {:?}", err);
+        let device = device.open().unwrap();
+        let result = match opt.target {
+            Target::Ram => cyusb::program_fx3_ram_with_progress(
+                &device,
+                &opt.image,
+                &mut |event| log_progress(event),
+            ),
+            Target::I2c => cyusb::program_fx3_i2c_with_progress(
+                &device,
+                &opt.image,
+                opt.force,
+                &mut |event| log_progress(event),
+            ),
+            Target::Spi => cyusb::program_fx3_spi_with_progress(
+                &device,
+                &opt.image,
+                opt.force,
+                &mut |event| log_progress(event),
+            ),
+        };
+        if let Err(err) = result {
+            error!("programming failed: {:?}", err);
             exit(1);
         }
     } else {
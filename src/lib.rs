@@ -14,6 +14,94 @@ pub enum Error {
     InvalidChecksum,
     TruncatedData(TryFromSliceError),
     UsbError(rusb::Error),
+    /// Data read back from the target did not match what was written
+    VerifyFailed { address: u32 },
+    /// A sector erase did not finish within the allotted number of polls
+    EraseTimeout,
+    /// The image ended before a header, record or checksum field could
+    /// be read
+    UnexpectedEof,
+    /// A record's length would read past the end of the image
+    RecordTooLong { offset: usize },
+    /// The attached device isn't a Cypress FX3
+    UnsupportedDevice { vid: u16, pid: u16 },
+    /// The image is too large to write without overlapping the
+    /// reserved hash-slot region
+    ImageTooLarge { max_size: u32 },
+}
+
+/// USB vendor ID of a Cypress FX3 in bootloader mode.
+pub const FX3_VENDOR_ID: u16 = 0x04b4;
+
+/// USB product ID of a Cypress FX3 in bootloader mode.
+pub const FX3_PRODUCT_ID: u16 = 0x00f3;
+
+/// Maximum number of bytes the FX3 flash-programmer firmware accepts
+/// in a single vendor command.
+const MAX_WRITE_SIZE: u32 = 2048;
+
+/// I2C EEPROMs are written in 64-byte pages; a single vendor command
+/// must not straddle a page boundary.
+const I2C_PAGE_SIZE: u32 = 64;
+
+/// Each I2C slave address only addresses 64 KiB of EEPROM; beyond that
+/// the programmer firmware expects the slave selector to be bumped.
+const I2C_SLAVE_SIZE: u32 = 0x1_0000;
+
+/// SPI NOR flash is programmed in 256-byte pages; a single
+/// page-program command must not straddle a page boundary.
+const SPI_PAGE_SIZE: u32 = 256;
+
+/// SPI NOR flash is erased in 64 KiB sectors.
+const SPI_SECTOR_SIZE: u32 = 0x1_0000;
+
+/// How many times to poll the erase-status request before giving up.
+const SPI_ERASE_MAX_RETRIES: u32 = 50;
+
+/// Delay between erase-status polls.
+const SPI_ERASE_POLL_DELAY: Duration = Duration::from_millis(50);
+
+/// Size in bytes of the MD5 digest stashed in the hash slot.
+const HASH_SIZE: usize = 16;
+
+/// Reserved (out of band) I2C slave used to stash the digest of the
+/// last image that was written, so repeat runs can skip reprogramming.
+const I2C_HASH_SLOT_SLAVE: u8 = 0xff;
+const I2C_HASH_SLOT_ADDRESS: u16 = 0;
+
+/// Largest image that fits before the hash slot's slave address,
+/// leaving `I2C_HASH_SLOT_SLAVE` free for the digest.
+const I2C_MAX_IMAGE_SIZE: u32 = I2C_HASH_SLOT_SLAVE as u32 * I2C_SLAVE_SIZE;
+
+/// Reserved sector used to stash the digest of the last image that was
+/// written to SPI flash, so repeat runs can skip reprogramming.
+const SPI_HASH_SLOT_ADDRESS: u32 = 0xffff_0000;
+
+/// Largest image that fits before the hash-slot sector.
+const SPI_MAX_IMAGE_SIZE: u32 = SPI_HASH_SLOT_ADDRESS;
+
+/// Digest used by the hash-slot optimization to detect that the image
+/// already on the target matches the one about to be written.
+fn digest(data: &[u8]) -> [u8; HASH_SIZE] {
+    md5::compute(data).0
+}
+
+/// Which step of programming a `ProgressEvent` was reported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Erase,
+    Write,
+    Verify,
+}
+
+/// Reports progress through a long-running transfer loop.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressEvent {
+    pub phase: Phase,
+    /// Target address the event applies to.
+    pub address: u32,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
 }
 
 struct Checksum {
@@ -39,6 +127,18 @@ impl Checksum {
     }
 }
 
+/// Check that `device` is a Cypress FX3 in bootloader mode.
+pub fn check_device_id(device: &DeviceHandle) -> Result<(), Error> {
+    let desc =
+        device.device().device_descriptor().map_err(Error::UsbError)?;
+    let vid = desc.vendor_id();
+    let pid = desc.product_id();
+    if vid != FX3_VENDOR_ID || pid != FX3_PRODUCT_ID {
+        return Err(Error::UnsupportedDevice { vid, pid });
+    }
+    Ok(())
+}
+
 fn write_control(
     device: &DeviceHandle,
     address: u32,
@@ -57,7 +157,25 @@ fn write_control(
     Ok(bytes_written)
 }
 
-fn control_transfer(
+fn read_control(
+    device: &DeviceHandle,
+    address: u32,
+    buf: &mut [u8],
+) -> Result<usize, Error> {
+    device
+        .read_control(
+            /*request_type=*/ 0xc0,
+            /*request=*/ 0xa0,
+            /*value=*/ (address & 0x0000ffff) as u16,
+            /*index=*/ (address >> 16) as u16,
+            /*buf=*/ buf,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)
+}
+
+/// Write `data` to FX3 RAM starting at `address`.
+pub fn write_ram(
     device: &DeviceHandle,
     mut address: u32,
     data: &[u8],
@@ -84,69 +202,632 @@ fn control_transfer(
     Ok(())
 }
 
+/// Read `data.len()` bytes of FX3 RAM starting at `address`.
+pub fn read_ram(
+    device: &DeviceHandle,
+    mut address: u32,
+    data: &mut [u8],
+) -> Result<(), Error> {
+    let mut balance = data.len() as u32;
+    let mut offset = 0;
+
+    while balance > 0 {
+        let b = if balance > 4096 { 4096 } else { balance };
+
+        let bytes_read = read_control(
+            device,
+            address,
+            &mut data[offset as usize..(offset + b) as usize],
+        )? as u32;
+
+        address += bytes_read;
+        balance -= bytes_read;
+        offset += bytes_read;
+    }
+
+    Ok(())
+}
+
+/// Jump to `entry_address` and start executing the downloaded firmware.
+pub fn start_firmware(
+    device: &DeviceHandle,
+    entry_address: u32,
+) -> Result<(), Error> {
+    write_control(device, entry_address, &[])?;
+    Ok(())
+}
+
+/// One `(address, data)` pair from a parsed firmware image, to be
+/// written to `address` on the target.
+struct FirmwareRecord<'a> {
+    address: u32,
+    data: &'a [u8],
+}
+
+/// A parsed and validated FX3 firmware image.
+///
+/// Every field is range-checked against the buffer it was parsed from,
+/// so a short or corrupt file produces an `Error` here rather than
+/// panicking later when it is used.
+pub struct FirmwareImage<'a> {
+    raw: &'a [u8],
+    records: Vec<FirmwareRecord<'a>>,
+    entry_address: u32,
+}
+
+impl<'a> FirmwareImage<'a> {
+    /// Parse and validate a firmware image.
+    pub fn parse(raw: &'a [u8]) -> Result<FirmwareImage<'a>, Error> {
+        if raw.len() < 4 {
+            return Err(Error::UnexpectedEof);
+        }
+
+        // Program must start with "CY"
+        if raw[0] != b'C' || raw[1] != b'Y' {
+            return Err(Error::MissingMagic);
+        }
+
+        // Check that the image contains executable code
+        if (raw[2] & 0x01) != 0 {
+            return Err(Error::NotExecutable);
+        }
+
+        // Check for a normal FW binary with checksum
+        if raw[3] != 0xb0 {
+            return Err(Error::AbnormalFirmware);
+        }
+
+        let mut offset = 4;
+        let mut checksum = Checksum::new();
+        let mut records = Vec::new();
+        let entry_address;
+
+        loop {
+            let record_offset = offset;
+            let length = read_u32_checked(raw, &mut offset)?;
+            let address = read_u32_checked(raw, &mut offset)?;
+
+            if length == 0 {
+                entry_address = address;
+                break;
+            }
+
+            let byte_len = (length as usize)
+                .checked_mul(4)
+                .ok_or(Error::RecordTooLong { offset: record_offset })?;
+            if byte_len > raw.len() - offset {
+                return Err(Error::RecordTooLong { offset: record_offset });
+            }
+
+            let data = &raw[offset..offset + byte_len];
+            offset += byte_len;
+
+            checksum.update(data)?;
+            records.push(FirmwareRecord { address, data });
+        }
+
+        let expected_checksum = read_u32_checked(raw, &mut offset)?;
+        if expected_checksum != checksum.value {
+            return Err(Error::InvalidChecksum);
+        }
+
+        Ok(FirmwareImage { raw, records, entry_address })
+    }
+
+    /// The raw bytes of the image, as they should be written
+    /// sequentially to an I2C EEPROM or SPI flash.
+    fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    fn entry_address(&self) -> u32 {
+        self.entry_address
+    }
+
+    fn records(&self) -> &[FirmwareRecord<'a>] {
+        &self.records
+    }
+}
+
+/// Read a little-endian `u32` at `*offset`, advancing it by 4.
+///
+/// Returns `Error::UnexpectedEof` rather than panicking if the buffer
+/// is too short.
+fn read_u32_checked(buf: &[u8], offset: &mut usize) -> Result<u32, Error> {
+    let end = offset.checked_add(4).ok_or(Error::UnexpectedEof)?;
+    let chunk = buf.get(*offset..end).ok_or(Error::UnexpectedEof)?;
+    let val =
+        u32::from_le_bytes(chunk.try_into().map_err(Error::TruncatedData)?);
+    *offset = end;
+    Ok(val)
+}
+
 /// Download firmware to RAM on a Cypress FX3
 pub fn program_fx3_ram(
     device: &DeviceHandle,
     path: &Path,
 ) -> Result<(), Error> {
+    program_fx3_ram_with_progress(device, path, &mut |_| {})
+}
+
+/// Like [`program_fx3_ram`], but calls `progress` after each record is
+/// transferred so callers can show a progress indicator.
+pub fn program_fx3_ram_with_progress(
+    device: &DeviceHandle,
+    path: &Path,
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<(), Error> {
+    check_device_id(device)?;
+
+    // Firmware files should be quite small, so just load the whole
+    // thing in memory
+    let program = fs::read(path).map_err(Error::IoError)?;
+    let image = FirmwareImage::parse(&program)?;
+
+    let bytes_total: u64 =
+        image.records().iter().map(|r| r.data.len() as u64).sum();
+    let mut bytes_done: u64 = 0;
+
+    // Transfer the program to the FX3
+    for record in image.records() {
+        write_ram(device, record.address, record.data)?;
+
+        bytes_done += record.data.len() as u64;
+        progress(ProgressEvent {
+            phase: Phase::Write,
+            address: record.address,
+            bytes_done,
+            bytes_total,
+        });
+    }
+
+    thread::sleep(Duration::from_secs(1));
+
+    start_firmware(device, image.entry_address())?;
+
+    Ok(())
+}
+
+fn i2c_write(
+    device: &DeviceHandle,
+    slave: u8,
+    address: u16,
+    data: &[u8],
+) -> Result<usize, Error> {
+    device
+        .write_control(
+            /*request_type=*/ 0x40,
+            /*request=*/ 0xba,
+            /*value=*/ address,
+            /*index=*/ (slave as u16) << 8,
+            /*buf=*/ data,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)
+}
+
+fn i2c_read(
+    device: &DeviceHandle,
+    slave: u8,
+    address: u16,
+    buf: &mut [u8],
+) -> Result<usize, Error> {
+    device
+        .read_control(
+            /*request_type=*/ 0xc0,
+            /*request=*/ 0xbb,
+            /*value=*/ address,
+            /*index=*/ (slave as u16) << 8,
+            /*buf=*/ buf,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)
+}
+
+/// Download firmware to an I2C EEPROM through the FX3 flash-programmer
+/// firmware, verifying every chunk after it is written.
+///
+/// Unless `force` is set, the image's digest is checked against the
+/// one already stored in the hash slot first, and reprogramming is
+/// skipped entirely if they match.
+pub fn program_fx3_i2c(
+    device: &DeviceHandle,
+    path: &Path,
+    force: bool,
+) -> Result<(), Error> {
+    program_fx3_i2c_with_progress(device, path, force, &mut |_| {})
+}
+
+/// Like [`program_fx3_i2c`], but calls `progress` after each chunk is
+/// written and verified so callers can show a progress indicator.
+pub fn program_fx3_i2c_with_progress(
+    device: &DeviceHandle,
+    path: &Path,
+    force: bool,
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<(), Error> {
+    check_device_id(device)?;
+
     // Firmware files should be quite small, so just load the whole
     // thing in memory
     let program = fs::read(path).map_err(Error::IoError)?;
+    let image = FirmwareImage::parse(&program)?;
+    let raw = image.raw();
 
-    // Program must start with "CY"
-    if program[0] != b'C' || program[1] != b'Y' {
-        return Err(Error::MissingMagic);
+    if raw.len() as u32 > I2C_MAX_IMAGE_SIZE {
+        return Err(Error::ImageTooLarge { max_size: I2C_MAX_IMAGE_SIZE });
     }
 
-    // Check that the image contains executable code
-    if (program[2] & 0x01) != 0 {
-        return Err(Error::NotExecutable);
+    let image_digest = digest(raw);
+    let bytes_total = raw.len() as u64;
+
+    if !force {
+        let mut stored_digest = [0u8; HASH_SIZE];
+        i2c_read(
+            device,
+            I2C_HASH_SLOT_SLAVE,
+            I2C_HASH_SLOT_ADDRESS,
+            &mut stored_digest,
+        )?;
+        if stored_digest == image_digest {
+            return Ok(());
+        }
     }
 
-    // Check for a normal FW binary with checksum
-    if program[3] != 0xb0 {
-        return Err(Error::AbnormalFirmware);
+    let mut slave: u8 = 0;
+    let mut address: u32 = 0;
+    let mut offset = 0;
+
+    while offset < raw.len() {
+        // A single write may not cross a page boundary, may not cross
+        // the 64 KiB boundary of the current slave address, and may
+        // not exceed what the programmer firmware accepts in one
+        // vendor command.
+        let until_page = I2C_PAGE_SIZE - (address % I2C_PAGE_SIZE);
+        let until_slave = I2C_SLAVE_SIZE - address;
+        let remaining = (raw.len() - offset) as u32;
+        let chunk_len = remaining
+            .min(until_page)
+            .min(until_slave)
+            .min(MAX_WRITE_SIZE) as usize;
+
+        let chunk = &raw[offset..offset + chunk_len];
+        i2c_write(device, slave, address as u16, chunk)?;
+
+        progress(ProgressEvent {
+            phase: Phase::Write,
+            address,
+            bytes_done: offset as u64 + chunk_len as u64,
+            bytes_total,
+        });
+
+        let mut readback = vec![0u8; chunk_len];
+        i2c_read(device, slave, address as u16, &mut readback)?;
+        if readback != chunk {
+            return Err(Error::VerifyFailed { address });
+        }
+
+        progress(ProgressEvent {
+            phase: Phase::Verify,
+            address,
+            bytes_done: offset as u64 + chunk_len as u64,
+            bytes_total,
+        });
+
+        offset += chunk_len;
+        address += chunk_len as u32;
+        if address >= I2C_SLAVE_SIZE {
+            address = 0;
+            slave += 1;
+        }
     }
 
-    let mut offset = 4;
-    let mut checksum = Checksum::new();
-    let entry_address;
+    i2c_write(
+        device,
+        I2C_HASH_SLOT_SLAVE,
+        I2C_HASH_SLOT_ADDRESS,
+        &image_digest,
+    )?;
 
-    let read_u32 = |offset: &mut usize| {
-        let chunk = &program[*offset..*offset + 4];
-        let val =
-            u32::from_le_bytes(chunk.try_into().map_err(Error::TruncatedData)?);
-        *offset += 4;
-        Ok(val)
-    };
+    Ok(())
+}
 
-    // Transfer the program to the FX3
-    loop {
-        let length = read_u32(&mut offset)?;
-        let address = read_u32(&mut offset)?;
+fn spi_write(
+    device: &DeviceHandle,
+    address: u32,
+    data: &[u8],
+) -> Result<usize, Error> {
+    device
+        .write_control(
+            /*request_type=*/ 0x40,
+            /*request=*/ 0xbc,
+            /*value=*/ (address & 0xffff) as u16,
+            /*index=*/ (address >> 16) as u16,
+            /*buf=*/ data,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)
+}
 
-        if length == 0 {
-            entry_address = address;
-            break;
-        } else {
-            let data = &program[offset..offset + (length as usize) * 4];
-            offset += (length as usize) * 4;
+fn spi_read(
+    device: &DeviceHandle,
+    address: u32,
+    buf: &mut [u8],
+) -> Result<usize, Error> {
+    device
+        .read_control(
+            /*request_type=*/ 0xc0,
+            /*request=*/ 0xbd,
+            /*value=*/ (address & 0xffff) as u16,
+            /*index=*/ (address >> 16) as u16,
+            /*buf=*/ buf,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)
+}
 
-            checksum.update(data)?;
+fn spi_erase_sector(device: &DeviceHandle, address: u32) -> Result<(), Error> {
+    device
+        .write_control(
+            /*request_type=*/ 0x40,
+            /*request=*/ 0xbe,
+            /*value=*/ (address & 0xffff) as u16,
+            /*index=*/ (address >> 16) as u16,
+            /*buf=*/ &[],
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)?;
+    Ok(())
+}
 
-            control_transfer(device, address, data)?;
+/// Returns true while the flash is still busy with an erase or write.
+fn spi_is_busy(device: &DeviceHandle) -> Result<bool, Error> {
+    let mut status = [0u8; 1];
+    device
+        .read_control(
+            /*request_type=*/ 0xc0,
+            /*request=*/ 0xbf,
+            /*value=*/ 0,
+            /*index=*/ 0,
+            /*buf=*/ &mut status,
+            /*timeout=*/ Duration::from_secs(1),
+        )
+        .map_err(Error::UsbError)?;
+    Ok((status[0] & 0x01) != 0)
+}
+
+fn spi_erase_sector_and_wait(
+    device: &DeviceHandle,
+    address: u32,
+) -> Result<(), Error> {
+    spi_erase_sector(device, address)?;
+
+    for _ in 0..SPI_ERASE_MAX_RETRIES {
+        if !spi_is_busy(device)? {
+            return Ok(());
         }
+        thread::sleep(SPI_ERASE_POLL_DELAY);
     }
 
-    // Read checksum
-    let expected_checksum = read_u32(&mut offset)?;
-    if expected_checksum != checksum.value {
-        return Err(Error::InvalidChecksum);
+    Err(Error::EraseTimeout)
+}
+
+/// Download firmware to SPI NOR flash through the FX3 flash-programmer
+/// firmware, erasing every touched sector first and verifying every
+/// written chunk.
+///
+/// Unless `force` is set, the image's digest is checked against the
+/// one already stored in the hash slot first, and reprogramming is
+/// skipped entirely if they match.
+pub fn program_fx3_spi(
+    device: &DeviceHandle,
+    path: &Path,
+    force: bool,
+) -> Result<(), Error> {
+    program_fx3_spi_with_progress(device, path, force, &mut |_| {})
+}
+
+/// Like [`program_fx3_spi`], but calls `progress` during erase and
+/// after each chunk is written and verified so callers can show a
+/// progress indicator.
+pub fn program_fx3_spi_with_progress(
+    device: &DeviceHandle,
+    path: &Path,
+    force: bool,
+    progress: &mut dyn FnMut(ProgressEvent),
+) -> Result<(), Error> {
+    check_device_id(device)?;
+
+    // Firmware files should be quite small, so just load the whole
+    // thing in memory
+    let program = fs::read(path).map_err(Error::IoError)?;
+    let image = FirmwareImage::parse(&program)?;
+    let raw = image.raw();
+
+    if raw.len() as u32 > SPI_MAX_IMAGE_SIZE {
+        return Err(Error::ImageTooLarge { max_size: SPI_MAX_IMAGE_SIZE });
     }
 
-    thread::sleep(Duration::from_secs(1));
+    let image_digest = digest(raw);
+    let bytes_total = raw.len() as u64;
 
-    write_control(device, entry_address, &[])?;
+    if !force {
+        let mut stored_digest = [0u8; HASH_SIZE];
+        spi_read(device, SPI_HASH_SLOT_ADDRESS, &mut stored_digest)?;
+        if stored_digest == image_digest {
+            return Ok(());
+        }
+    }
+
+    let image_len = raw.len() as u32;
+
+    let mut sector_address = 0;
+    while sector_address < image_len {
+        spi_erase_sector_and_wait(device, sector_address)?;
+
+        progress(ProgressEvent {
+            phase: Phase::Erase,
+            address: sector_address,
+            bytes_done: (sector_address + SPI_SECTOR_SIZE).min(image_len)
+                as u64,
+            bytes_total: image_len as u64,
+        });
+
+        sector_address += SPI_SECTOR_SIZE;
+    }
+
+    let mut offset = 0;
+    let mut address: u32 = 0;
+
+    while offset < raw.len() {
+        // A single page-program command may not cross a page
+        // boundary, and may not exceed what the programmer firmware
+        // accepts in one vendor command.
+        let until_page = SPI_PAGE_SIZE - (address % SPI_PAGE_SIZE);
+        let remaining = (raw.len() - offset) as u32;
+        let chunk_len = remaining.min(until_page).min(MAX_WRITE_SIZE) as usize;
+
+        let chunk = &raw[offset..offset + chunk_len];
+        spi_write(device, address, chunk)?;
+
+        progress(ProgressEvent {
+            phase: Phase::Write,
+            address,
+            bytes_done: offset as u64 + chunk_len as u64,
+            bytes_total,
+        });
+
+        let mut readback = vec![0u8; chunk_len];
+        spi_read(device, address, &mut readback)?;
+        if readback != chunk {
+            return Err(Error::VerifyFailed { address });
+        }
+
+        progress(ProgressEvent {
+            phase: Phase::Verify,
+            address,
+            bytes_done: offset as u64 + chunk_len as u64,
+            bytes_total,
+        });
+
+        offset += chunk_len;
+        address += chunk_len as u32;
+    }
+
+    spi_erase_sector_and_wait(device, SPI_HASH_SLOT_ADDRESS)?;
+    spi_write(device, SPI_HASH_SLOT_ADDRESS, &image_digest)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(flags: u8, fw_byte: u8) -> Vec<u8> {
+        vec![b'C', b'Y', flags, fw_byte]
+    }
+
+    #[test]
+    fn parse_rejects_short_buffer() {
+        let raw = [b'C', b'Y', 0];
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_bad_magic() {
+        let raw = [b'X', b'Y', 0, 0xb0];
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::MissingMagic)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_non_executable() {
+        let raw = header(0x01, 0xb0);
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::NotExecutable)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_abnormal_firmware() {
+        let raw = header(0x00, 0x00);
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::AbnormalFirmware)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_overflowing_record_length() {
+        let mut raw = header(0x00, 0xb0);
+        raw.extend_from_slice(&u32::MAX.to_le_bytes()); // length
+        raw.extend_from_slice(&0u32.to_le_bytes()); // address
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::RecordTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_record_past_end_of_buffer() {
+        let mut raw = header(0x00, 0xb0);
+        raw.extend_from_slice(&10u32.to_le_bytes()); // length (40 bytes)
+        raw.extend_from_slice(&0u32.to_le_bytes()); // address
+        // No data bytes follow, so the record runs off the end.
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::RecordTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_missing_checksum() {
+        let mut raw = header(0x00, 0xb0);
+        raw.extend_from_slice(&0u32.to_le_bytes()); // length = 0 (terminator)
+        raw.extend_from_slice(&0x1234u32.to_le_bytes()); // entry address
+        // Checksum word is missing entirely.
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_bad_checksum() {
+        let mut raw = header(0x00, 0xb0);
+        raw.extend_from_slice(&1u32.to_le_bytes()); // length = 1 word
+        raw.extend_from_slice(&0u32.to_le_bytes()); // address
+        raw.extend_from_slice(&0xaaaa_aaaau32.to_le_bytes()); // data word
+        raw.extend_from_slice(&0u32.to_le_bytes()); // terminator length
+        raw.extend_from_slice(&0u32.to_le_bytes()); // entry address
+        raw.extend_from_slice(&0u32.to_le_bytes()); // wrong checksum
+        assert!(matches!(
+            FirmwareImage::parse(&raw),
+            Err(Error::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn parse_accepts_valid_image() {
+        let mut raw = header(0x00, 0xb0);
+        raw.extend_from_slice(&1u32.to_le_bytes()); // length = 1 word
+        raw.extend_from_slice(&0x1000u32.to_le_bytes()); // address
+        raw.extend_from_slice(&0xaaaa_aaaau32.to_le_bytes()); // data word
+        raw.extend_from_slice(&0u32.to_le_bytes()); // terminator length
+        raw.extend_from_slice(&0x5000u32.to_le_bytes()); // entry address
+        raw.extend_from_slice(&0xaaaa_aaaau32.to_le_bytes()); // checksum
+
+        let image = FirmwareImage::parse(&raw).unwrap();
+        assert_eq!(image.entry_address(), 0x5000);
+        assert_eq!(image.records().len(), 1);
+        assert_eq!(image.records()[0].address, 0x1000);
+        assert_eq!(image.raw(), &raw[..]);
+    }
+}